@@ -2,6 +2,7 @@ mod utils;
 
 use dubble::DoubleBuffered;
 use std::cmp::Ordering;
+use std::collections::HashSet;
 use wasm_bindgen::prelude::*;
 
 // To access the JS Math.random
@@ -37,6 +38,18 @@ const PULSAR: [i32; 96] = [
     1, -2, 6, -1, -4, -1, -3, -1, -2, -1, 2, -1, 3, -1, 4,
 ];
 
+/// Selects how `live_neighbor_count` treats neighbors that fall outside the
+/// grid.
+#[wasm_bindgen]
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum BoundaryMode {
+    /// The grid wraps around at the edges, so every universe is an infinite
+    /// torus (the original behavior).
+    Toroidal,
+    /// Out-of-range neighbors count as dead, so the grid has real walls.
+    Dead,
+}
+
 /// Main data structure to store the universe state.
 /// Uses a double buffered strategy to update the cells states.
 #[wasm_bindgen]
@@ -45,6 +58,31 @@ pub struct Universe {
     height: u32,
     cells: DoubleBuffered<FixedBitSet>,
     n_ticks: u32,
+    /// Bitmask where bit `n` (0..=8) is set if a dead cell with `n` live
+    /// neighbors is born.
+    birth: u16,
+    /// Bitmask where bit `n` (0..=8) is set if a live cell with `n` live
+    /// neighbors survives.
+    survival: u16,
+    /// Seed used by the last call to `reset_seeded`.
+    seed: u64,
+    /// Number of consecutive generations each cell has been alive, saturating
+    /// at `u16::MAX`. Reset to 0 as soon as a cell dies.
+    ages: Vec<u16>,
+    /// Whether `tick` scans only the cells that could possibly change
+    /// (`true`) or the whole grid every generation (`false`).
+    incremental: bool,
+    /// Candidate indices for the incremental tick: every currently alive
+    /// cell plus any cell that changed in the previous generation and its
+    /// in-grid neighbors under the current boundary mode. `tick_full` does not maintain this, so it is
+    /// re-seeded by `set_incremental` whenever incremental mode is enabled.
+    dirty: HashSet<usize>,
+    /// Number of alive cells as of the last tick.
+    live_count: u32,
+    /// Number of cells that flipped state during the last tick.
+    changed_count: u32,
+    /// How neighbors outside the grid are treated.
+    boundary: BoundaryMode,
 }
 
 #[wasm_bindgen]
@@ -65,6 +103,16 @@ impl Universe {
             height,
             cells,
             n_ticks: 1,
+            // Default to Conway's Game of Life (B3/S23)
+            birth: 1 << 3,
+            survival: (1 << 2) | (1 << 3),
+            seed: 0,
+            ages: vec![0; size],
+            incremental: false,
+            dirty: HashSet::new(),
+            live_count: 0,
+            changed_count: 0,
+            boundary: BoundaryMode::Toroidal,
         };
 
         // Randomly set the universe cells
@@ -98,6 +146,8 @@ impl Universe {
             }
             Ordering::Equal => {}
         }
+        self.ages = vec![0; new_size];
+        self.dirty.clear();
         self.reset();
     }
 
@@ -107,12 +157,38 @@ impl Universe {
             self.cells.set(i, js_sys::Math::random() < 0.5);
         }
         self.cells.update();
+        self.ages.iter_mut().for_each(|age| *age = 0);
+        self.seed_dirty_set();
+    }
+
+    /// Reset all the cells using a deterministic PRNG seeded with `seed`.
+    ///
+    /// Unlike `reset`, calling this again with the same seed reproduces the
+    /// exact same initial configuration, which makes benchmarks and bug
+    /// reports reproducible and lets users share starting worlds by a
+    /// single number.
+    pub fn reset_seeded(&mut self, seed: u64) {
+        self.seed = seed;
+        let mut rng = utils::SplitMix64::new(seed);
+        for i in 0..self.size() {
+            self.cells.set(i, rng.next_u64() & 1 == 1);
+        }
+        self.cells.update();
+        self.ages.iter_mut().for_each(|age| *age = 0);
+        self.seed_dirty_set();
+    }
+
+    /// Get the seed used by the last call to `reset_seeded`.
+    pub fn seed(&self) -> u64 {
+        self.seed
     }
 
     /// Reset the universe with all the cells dead.
     pub fn clear(&mut self) {
         self.cells.clear();
         self.cells.update();
+        self.ages.iter_mut().for_each(|age| *age = 0);
+        self.seed_dirty_set();
     }
 
     /// Set the number of world updates (ticks) per update.
@@ -120,6 +196,55 @@ impl Universe {
         self.n_ticks = ticks;
     }
 
+    /// Set the birth/survival rule from a Golly-style `B.../S...` rulestring
+    /// (e.g. `"B3/S23"` for Conway's Game of Life, `"B36/S23"` for HighLife).
+    ///
+    /// The `B` and `S` components may appear in either order and are matched
+    /// case-insensitively. Returns an error if a component is missing, a
+    /// digit is not a valid decimal, or a neighbor count is greater than 8.
+    ///
+    /// Rules with a `B0` digit are not supported by incremental ticking;
+    /// see `tick_incremental`.
+    pub fn set_rule(&mut self, rule: &str) -> Result<(), String> {
+        let (birth, survival) = parse_rule(rule)?;
+        self.birth = birth;
+        self.survival = survival;
+        Ok(())
+    }
+
+    /// Selects whether `tick` scans only the cells that could possibly
+    /// change (`true`) or the whole grid every generation (`false`).
+    ///
+    /// Incremental ticking wins on large, mostly-empty universes; the plain
+    /// full scan wins once a sizable fraction of the grid is alive. Use
+    /// `live_count`/`changed_count` after a tick to decide which applies.
+    ///
+    /// `tick_full` does not keep the dirty set up to date, so enabling
+    /// incremental mode re-seeds it from the current grid state to avoid
+    /// missing births/deaths that happened during full-scan ticks.
+    pub fn set_incremental(&mut self, incremental: bool) {
+        if incremental && !self.incremental {
+            self.seed_dirty_set();
+        }
+        self.incremental = incremental;
+    }
+
+    /// Number of alive cells as of the last tick.
+    pub fn live_count(&self) -> u32 {
+        self.live_count
+    }
+
+    /// Number of cells that changed state during the last tick.
+    pub fn changed_count(&self) -> u32 {
+        self.changed_count
+    }
+
+    /// Selects how neighbors outside the grid are treated: `Toroidal` wraps
+    /// around the edges, `Dead` treats them as dead cells (real walls).
+    pub fn set_boundary(&mut self, mode: BoundaryMode) {
+        self.boundary = mode;
+    }
+
     /// Set the width of the universe.
     ///
     /// Resets all cells to the dead state.
@@ -143,56 +268,110 @@ impl Universe {
         self.cells.as_slice().as_ptr()
     }
 
+    /// Get a pointer to the cell ages data, i.e. the number of consecutive
+    /// generations each cell has been alive. Lets JS render heat-map style
+    /// coloring without crossing the wasm boundary per cell.
+    pub fn ages(&self) -> *const u16 {
+        self.ages.as_ptr()
+    }
+
     /// Get the index of a cell in the data array.
     fn get_index(&self, row: u32, column: u32) -> usize {
         (row * self.width + column) as usize
     }
 
-    /// Returns the number of alive neighbors for a given cell.
-    fn live_neighbor_count(&self, row: u32, column: u32) -> u8 {
-        let mut count = 0;
-
-        let north = if row == 0 { self.height - 1 } else { row - 1 };
-
-        let south = if row == self.height - 1 { 0 } else { row + 1 };
-
-        let west = if column == 0 {
-            self.width - 1
-        } else {
-            column - 1
-        };
-
-        let east = if column == self.width - 1 {
-            0
-        } else {
-            column + 1
-        };
-
-        let nw = self.get_index(north, west);
-        count += self.cells[nw] as u8;
-
-        let n = self.get_index(north, column);
-        count += self.cells[n] as u8;
+    /// Get the row/column of a cell from its index in the data array.
+    fn get_row_col(&self, idx: usize) -> (u32, u32) {
+        let idx = idx as u32;
+        (idx / self.width, idx % self.width)
+    }
 
-        let ne = self.get_index(north, east);
-        count += self.cells[ne] as u8;
+    /// Resolves a (possibly out-of-range) row/column to a concrete in-grid
+    /// position according to the current boundary mode: `Toroidal` wraps
+    /// around the edges, `Dead` returns `None` once the position falls
+    /// outside the grid.
+    fn resolve_position(&self, row: i32, column: i32) -> Option<(u32, u32)> {
+        match self.boundary {
+            BoundaryMode::Toroidal => Some((
+                row.rem_euclid(self.height as i32) as u32,
+                column.rem_euclid(self.width as i32) as u32,
+            )),
+            BoundaryMode::Dead => {
+                if row < 0 || column < 0 || row >= self.height as i32 || column >= self.width as i32
+                {
+                    None
+                } else {
+                    Some((row as u32, column as u32))
+                }
+            }
+        }
+    }
 
-        let w = self.get_index(row, west);
-        count += self.cells[w] as u8;
+    /// Returns the index of the neighbor at offset `(dr, dc)` from
+    /// `(row, column)`, or `None` if it falls outside the grid in `Dead`
+    /// boundary mode.
+    fn neighbor_index(&self, row: u32, column: u32, dr: i32, dc: i32) -> Option<usize> {
+        self.resolve_position(row as i32 + dr, column as i32 + dc)
+            .map(|(r, c)| self.get_index(r, c))
+    }
 
-        let e = self.get_index(row, east);
-        count += self.cells[e] as u8;
+    /// Returns the indices of the (up to) 8 neighbors of a cell that fall
+    /// within the grid under the current boundary mode.
+    fn neighbor_indices(&self, row: u32, column: u32) -> impl Iterator<Item = usize> + '_ {
+        const OFFSETS: [(i32, i32); 8] = [
+            (-1, -1),
+            (-1, 0),
+            (-1, 1),
+            (0, -1),
+            (0, 1),
+            (1, -1),
+            (1, 0),
+            (1, 1),
+        ];
+        OFFSETS
+            .iter()
+            .filter_map(move |&(dr, dc)| self.neighbor_index(row, column, dr, dc))
+    }
 
-        let sw = self.get_index(south, west);
-        count += self.cells[sw] as u8;
+    /// Returns the number of alive neighbors for a given cell.
+    fn live_neighbor_count(&self, row: u32, column: u32) -> u8 {
+        self.neighbor_indices(row, column)
+            .map(|idx| self.cells[idx] as u8)
+            .sum()
+    }
 
-        let s = self.get_index(south, column);
-        count += self.cells[s] as u8;
+    /// Marks `idx` and its in-grid neighbors as candidates for the next
+    /// incremental tick, without discarding the rest of the dirty set.
+    fn mark_neighborhood_dirty(&mut self, idx: usize) {
+        let (row, col) = self.get_row_col(idx);
+        self.dirty.insert(idx);
+        for neighbor in self.neighbor_indices(row, col).collect::<Vec<_>>() {
+            self.dirty.insert(neighbor);
+        }
+    }
 
-        let se = self.get_index(south, east);
-        count += self.cells[se] as u8;
+    /// Rebuilds the dirty set from scratch out of every currently alive
+    /// cell (and their neighbors), for use after a wholesale state change.
+    fn seed_dirty_set(&mut self) {
+        self.dirty.clear();
+        for idx in 0..self.size() {
+            if self.cells[idx] {
+                self.mark_neighborhood_dirty(idx);
+            }
+        }
+    }
 
-        count
+    /// Applies the birth/survival rule to a single cell, returning whether
+    /// it is alive in the next generation.
+    fn next_cell_state(&self, idx: usize, row: u32, col: u32) -> bool {
+        let cell = self.cells[idx]; // From read buffer
+        let n = self.live_neighbor_count(row, col) as u16;
+        let alive = if cell {
+            (self.survival >> n) & 1
+        } else {
+            (self.birth >> n) & 1
+        };
+        alive == 1
     }
 
     /// Compute the next universe state.
@@ -206,35 +385,104 @@ impl Universe {
     }
 
     /// Update the universe state by one tick.
+    ///
+    /// Scans the whole grid, or only the cells that could possibly change,
+    /// depending on `set_incremental`.
     pub fn tick(&mut self) {
+        if self.incremental {
+            self.tick_incremental();
+        } else {
+            self.tick_full();
+        }
+    }
+
+    /// Updates every cell in the grid, regardless of whether it changed.
+    fn tick_full(&mut self) {
+        self.live_count = 0;
+        self.changed_count = 0;
+
         for row in 0..self.height {
             for col in 0..self.width {
                 let idx = self.get_index(row, col);
-                let cell = self.cells[idx]; // From read buffer
-                let live_neighbors = self.live_neighbor_count(row, col);
-
-                // Update the write buffer
-                self.cells.set(
-                    idx,
-                    match (cell, live_neighbors) {
-                        (true, x) if x < 2 => false,
-                        (true, 2) | (true, 3) => true,
-                        (true, x) if x > 3 => false,
-                        (false, 3) => true,
-                        (otherwise, _) => otherwise,
-                    },
-                );
+                let was_alive = self.cells[idx];
+                let alive = self.next_cell_state(idx, row, col);
+
+                self.cells.set(idx, alive);
+                self.ages[idx] = if alive {
+                    self.ages[idx].saturating_add(1)
+                } else {
+                    0
+                };
+
+                if alive {
+                    self.live_count += 1;
+                }
+                if alive != was_alive {
+                    self.changed_count += 1;
+                }
             }
         }
 
         self.cells.update();
     }
 
+    /// Updates only the cells in the dirty set: every currently alive cell
+    /// plus the in-grid neighbors of any cell that changed in the previous
+    /// generation. A cell that flips also stays dirty itself, so rules
+    /// where birth and survival aren't nested (e.g. HighLife's `B36/S23`)
+    /// still re-evaluate correctly.
+    ///
+    /// Not supported: rules with a `B0` digit (dead cells with zero live
+    /// neighbors are born). There, every dead cell in the grid is a birth
+    /// candidate every generation, not just the neighborhood of a change,
+    /// which defeats the sparse tracking this optimization relies on. Use
+    /// the full scan (`set_incremental(false)`) for `B0` rules.
+    fn tick_incremental(&mut self) {
+        let mut next_dirty = HashSet::new();
+        self.live_count = 0;
+        self.changed_count = 0;
+
+        for &idx in &self.dirty {
+            let (row, col) = self.get_row_col(idx);
+            let was_alive = self.cells[idx];
+            let alive = self.next_cell_state(idx, row, col);
+
+            self.cells.set(idx, alive);
+            self.ages[idx] = if alive {
+                self.ages[idx].saturating_add(1)
+            } else {
+                0
+            };
+
+            if alive {
+                self.live_count += 1;
+                next_dirty.insert(idx);
+            }
+            if alive != was_alive {
+                self.changed_count += 1;
+                // Keep the flipped cell itself dirty too: under a rule
+                // where birth and survival aren't nested (B ⊄ S, e.g.
+                // HighLife's B36/S23), a cell that just died can still be reborn
+                // next generation, so it must stay a candidate even though
+                // it is currently dead.
+                next_dirty.insert(idx);
+                for neighbor in self.neighbor_indices(row, col) {
+                    next_dirty.insert(neighbor);
+                }
+            }
+        }
+
+        self.dirty = next_dirty;
+        self.cells.update();
+    }
+
     /// Toggle the state of a given cell.
     pub fn toggle_cell(&mut self, row: u32, column: u32) {
         let idx = self.get_index(row, column);
         self.cells.toggle(idx);
         self.cells.update();
+        self.ages[idx] = 0;
+        self.mark_neighborhood_dirty(idx);
     }
 
     /// Generic function to draw a figure given the figure definition (`coords`) and the
@@ -251,13 +499,19 @@ impl Universe {
         // Get the number of cells to set alive to create the figure
         let n_cells = coords.len() / 2;
         for i in 0..n_cells {
-            // Apply the coordinates offsets of the current cell
-            let aux_row = (row as i32 + coords[i * 2]).rem_euclid(self.height as i32) as u32;
-            let aux_col = (column as i32 + coords[i * 2 + 1]).rem_euclid(self.width as i32) as u32;
-            // Get the corresponding index in the data array
-            let idx = self.get_index(aux_row, aux_col);
-            // Set the cell alive
-            self.cells.insert(idx);
+            // Apply the coordinates offsets of the current cell. In `Dead`
+            // boundary mode, cells that fall outside the grid are dropped
+            // instead of wrapping around.
+            let aux_row = row as i32 + coords[i * 2];
+            let aux_col = column as i32 + coords[i * 2 + 1];
+            if let Some((aux_row, aux_col)) = self.resolve_position(aux_row, aux_col) {
+                // Get the corresponding index in the data array
+                let idx = self.get_index(aux_row, aux_col);
+                // Set the cell alive
+                self.cells.insert(idx);
+                self.ages[idx] = 0;
+                self.mark_neighborhood_dirty(idx);
+            }
         }
         self.cells.update();
     }
@@ -271,6 +525,116 @@ impl Universe {
     pub fn create_pulsar(&mut self, row: u32, column: u32) {
         self.create_figure(&PULSAR, row, column);
     }
+
+    /// Loads a pattern described in the Life RLE format at the position
+    /// provided.
+    ///
+    /// The RLE text may start with `#`-prefixed comment lines, followed by a
+    /// header line (`x = <w>, y = <h>, rule = <B.../S...>`) and a body of
+    /// run-length-encoded `b`/`o`/`$` tokens terminated by `!`. If the header
+    /// specifies a rule, it replaces the universe's current rule.
+    ///
+    /// # Arguments
+    ///
+    /// * `rle` - The pattern in Life RLE format.
+    /// * `row` - Y coordinate of the top-left corner to place the pattern at.
+    /// * `column` - X coordinate of the top-left corner to place the pattern at.
+    pub fn load_rle(&mut self, rle: &str, row: u32, column: u32) -> Result<(), String> {
+        let mut lines = rle
+            .lines()
+            .filter(|line| !line.trim_start().starts_with('#'));
+
+        let header = lines
+            .next()
+            .ok_or_else(|| "empty RLE pattern".to_string())?;
+        let (_width, _height, rule) = parse_rle_header(header)?;
+        if let Some(rule) = rule {
+            self.set_rule(&rule)?;
+        }
+
+        let body: String = lines.collect();
+
+        let mut dx: i32 = 0;
+        let mut dy: i32 = 0;
+        let mut run_len = String::new();
+
+        for c in body.chars() {
+            if c.is_ascii_digit() {
+                run_len.push(c);
+                continue;
+            }
+
+            let count: i32 = if run_len.is_empty() {
+                1
+            } else {
+                run_len
+                    .parse()
+                    .map_err(|_| format!("invalid run length \"{}\"", run_len))?
+            };
+            run_len.clear();
+
+            match c {
+                'b' | 'B' => dx += count,
+                'o' | 'O' => {
+                    for _ in 0..count {
+                        // In `Dead` boundary mode, cells that fall outside
+                        // the grid are dropped instead of wrapping around.
+                        if let Some((aux_row, aux_col)) =
+                            self.resolve_position(row as i32 + dy, column as i32 + dx)
+                        {
+                            let idx = self.get_index(aux_row, aux_col);
+                            self.cells.insert(idx);
+                            self.ages[idx] = 0;
+                            self.mark_neighborhood_dirty(idx);
+                        }
+                        dx += 1;
+                    }
+                }
+                '$' => {
+                    dy += count;
+                    dx = 0;
+                }
+                '!' => break,
+                c if c.is_whitespace() => {}
+                other => return Err(format!("unexpected RLE tag '{}'", other)),
+            }
+        }
+
+        self.cells.update();
+        Ok(())
+    }
+
+    /// Exports the current universe state as a Life RLE pattern.
+    pub fn to_rle(&self) -> String {
+        let mut rle = format!(
+            "x = {}, y = {}, rule = {}\n",
+            self.width,
+            self.height,
+            format_rule(self.birth, self.survival)
+        );
+
+        for row in 0..self.height {
+            let mut col = 0;
+            while col < self.width {
+                let idx = self.get_index(row, col);
+                let alive = self.cells[idx];
+                let mut run = 1;
+                while col + run < self.width && self.cells[self.get_index(row, col + run)] == alive
+                {
+                    run += 1;
+                }
+                if run > 1 {
+                    rle.push_str(&run.to_string());
+                }
+                rle.push(if alive { 'o' } else { 'b' });
+                col += run;
+            }
+            rle.push('$');
+        }
+        rle.push('!');
+
+        rle
+    }
 }
 
 impl Universe {
@@ -285,11 +649,112 @@ impl Universe {
         for (row, col) in cells.iter().cloned() {
             let idx = self.get_index(row, col);
             self.cells.set(idx, true);
+            self.mark_neighborhood_dirty(idx);
         }
         self.cells.update();
     }
 }
 
+/// Parses a Golly-style `B.../S...` rulestring into `(birth, survival)`
+/// bitmasks, where bit `n` (0..=8) is set if a cell with `n` live neighbors
+/// is born/survives.
+fn parse_rule(rule: &str) -> Result<(u16, u16), String> {
+    let mut birth = None;
+    let mut survival = None;
+
+    for part in rule.split('/') {
+        let mut chars = part.chars();
+        let tag = chars
+            .next()
+            .ok_or_else(|| format!("empty rule component in \"{}\"", rule))?;
+        let mask = parse_neighbor_mask(chars.as_str())?;
+
+        match tag.to_ascii_uppercase() {
+            'B' => birth = Some(mask),
+            'S' => survival = Some(mask),
+            other => return Err(format!("unexpected rule tag '{}' in \"{}\"", other, rule)),
+        }
+    }
+
+    match (birth, survival) {
+        (Some(birth), Some(survival)) => Ok((birth, survival)),
+        _ => Err(format!(
+            "rule \"{}\" must contain both a B and an S component",
+            rule
+        )),
+    }
+}
+
+/// Parses a run of decimal digits into a bitmask where bit `n` is set for
+/// each digit `n` present. Rejects digits greater than 8.
+fn parse_neighbor_mask(digits: &str) -> Result<u16, String> {
+    let mut mask = 0u16;
+    for c in digits.chars() {
+        let n = c
+            .to_digit(10)
+            .ok_or_else(|| format!("invalid neighbor count digit '{}'", c))?;
+        if n > 8 {
+            return Err(format!("neighbor count {} is out of range (0..=8)", n));
+        }
+        mask |= 1 << n;
+    }
+    Ok(mask)
+}
+
+/// Parses a Life RLE header line (`x = <w>, y = <h>[, rule = <B.../S...>]`)
+/// into its width, height, and optional rule string.
+fn parse_rle_header(header: &str) -> Result<(u32, u32, Option<String>), String> {
+    let mut width = None;
+    let mut height = None;
+    let mut rule = None;
+
+    for field in header.split(',') {
+        let mut parts = field.splitn(2, '=');
+        let key = parts.next().unwrap_or("").trim().to_ascii_lowercase();
+        let value = parts
+            .next()
+            .ok_or_else(|| format!("malformed RLE header field \"{}\"", field))?
+            .trim();
+
+        match key.as_str() {
+            "x" => {
+                width = Some(
+                    value
+                        .parse()
+                        .map_err(|_| format!("invalid RLE width \"{}\"", value))?,
+                )
+            }
+            "y" => {
+                height = Some(
+                    value
+                        .parse()
+                        .map_err(|_| format!("invalid RLE height \"{}\"", value))?,
+                )
+            }
+            "rule" => rule = Some(value.to_string()),
+            _ => {}
+        }
+    }
+
+    let width = width.ok_or_else(|| "missing \"x\" field in RLE header".to_string())?;
+    let height = height.ok_or_else(|| "missing \"y\" field in RLE header".to_string())?;
+    Ok((width, height, rule))
+}
+
+/// Formats a `(birth, survival)` bitmask pair back into a Golly-style
+/// `B.../S...` rulestring.
+fn format_rule(birth: u16, survival: u16) -> String {
+    format!("B{}/S{}", mask_to_digits(birth), mask_to_digits(survival))
+}
+
+/// Formats a neighbor-count bitmask as its sorted digit string.
+fn mask_to_digits(mask: u16) -> String {
+    (0..=8)
+        .filter(|n| (mask >> n) & 1 == 1)
+        .map(|n| n.to_string())
+        .collect()
+}
+
 impl Default for Universe {
     /// Default universe constructor with a size of 100x100.
     fn default() -> Self {